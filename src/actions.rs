@@ -0,0 +1,206 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use clap::ValueEnum;
+
+use crate::File;
+
+/// What to do with the non-canonical members of a duplicate group.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Action {
+    /// Print what the other actions would do, without touching anything.
+    #[default]
+    Report,
+    Delete,
+    Hardlink,
+    Symlink,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Action::Report => "report",
+            Action::Delete => "delete",
+            Action::Hardlink => "hardlink",
+            Action::Symlink => "symlink",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Pick the file to keep from a group of duplicates: shortest path, with the
+/// oldest modified time as the tiebreaker.
+fn pick_keeper(group: &[File]) -> usize {
+    group
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| {
+            a.name
+                .len()
+                .cmp(&b.name.len())
+                .then_with(|| a.metadata.modified().ok().cmp(&b.metadata.modified().ok()))
+        })
+        .map(|(i, _)| i)
+        .unwrap()
+}
+
+/// Create the replacement for `path` under a temp name in the same directory,
+/// then rename it over `path`, so a crash mid-way never leaves a missing file.
+fn replace_atomically<F>(path: &Path, make: F) -> io::Result<()>
+where
+    F: FnOnce(&Path) -> io::Result<()>,
+{
+    let parent = path.parent().unwrap_or_else(|| Path::new("."));
+    let temp_name = format!(
+        ".{}.organize-tmp",
+        path.file_name().unwrap_or_default().to_string_lossy()
+    );
+    let temp = parent.join(temp_name);
+    make(&temp)?;
+    fs::rename(&temp, path)
+}
+
+/// Apply `action` to every group of >1 files, keeping one canonical member and
+/// acting on the rest. `quiet` suppresses `Action::Report`'s dry-run stdout
+/// lines, for callers (e.g. `--format json`) whose stdout must stay
+/// machine-parseable.
+pub fn apply(action: Action, groups: Vec<Vec<File>>, quiet: bool) {
+    for group in groups {
+        if group.len() < 2 {
+            continue;
+        }
+        let keeper_idx = pick_keeper(&group);
+        let keeper = &group[keeper_idx];
+
+        if action == Action::Report && !quiet {
+            println!(
+                "{} duplicate(s) of {} (dry run, pass --action to apply)",
+                group.len() - 1,
+                keeper.name
+            );
+        }
+
+        for (i, file) in group.iter().enumerate() {
+            if i == keeper_idx {
+                continue;
+            }
+            let result = match action {
+                Action::Report => {
+                    if !quiet {
+                        println!("\t {}", file.name);
+                    }
+                    Ok(())
+                }
+                Action::Delete => fs::remove_file(&file.name),
+                Action::Hardlink => {
+                    replace_atomically(Path::new(&file.name), |temp| {
+                        fs::hard_link(&keeper.name, temp)
+                    })
+                }
+                Action::Symlink => replace_atomically(Path::new(&file.name), |temp| {
+                    std::os::unix::fs::symlink(&keeper.name, temp)
+                }),
+            };
+            if let Err(err) = result {
+                eprintln!("Failed to {} {}: {}", action, file.name, err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::os::unix::fs::MetadataExt;
+    use std::path::PathBuf;
+    use std::time::{Duration, SystemTime};
+
+    use super::*;
+
+    fn scratch_dir(label: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("fileorganizer-actions-test-{}-{}", label, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn make_file(path: &std::path::Path, contents: &str) -> File {
+        fs::write(path, contents).unwrap();
+        File {
+            name: path.to_string_lossy().to_string(),
+            metadata: fs::metadata(path).unwrap(),
+            partial_hash: None,
+        }
+    }
+
+    #[test]
+    fn pick_keeper_prefers_shorter_path() {
+        let dir = scratch_dir("keeper-length");
+        let short = make_file(&dir.join("a.txt"), "dup");
+        let long = make_file(&dir.join("a-much-longer-name.txt"), "dup");
+        let group = vec![long.clone(), short.clone()];
+        assert_eq!(group[pick_keeper(&group)].name, short.name);
+    }
+
+    #[test]
+    fn pick_keeper_breaks_ties_by_oldest_mtime() {
+        let dir = scratch_dir("keeper-mtime");
+        let older_path = dir.join("old.txt");
+        let newer_path = dir.join("new.txt");
+        make_file(&older_path, "dup");
+        let newer = make_file(&newer_path, "dup");
+
+        fs::File::open(&older_path)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(60))
+            .unwrap();
+        let older = File {
+            name: older_path.to_string_lossy().to_string(),
+            metadata: fs::metadata(&older_path).unwrap(),
+            partial_hash: None,
+        };
+
+        let group = vec![newer.clone(), older.clone()];
+        assert_eq!(group[pick_keeper(&group)].name, older.name);
+    }
+
+    #[test]
+    fn delete_removes_non_keeper_files() {
+        let dir = scratch_dir("delete");
+        let keeper = make_file(&dir.join("a.txt"), "dup");
+        let dup = make_file(&dir.join("b.txt"), "dup");
+
+        apply(Action::Delete, vec![vec![keeper, dup]], false);
+
+        assert!(dir.join("a.txt").exists());
+        assert!(!dir.join("b.txt").exists());
+    }
+
+    #[test]
+    fn hardlink_replaces_non_keeper_files() {
+        let dir = scratch_dir("hardlink");
+        let keeper = make_file(&dir.join("a.txt"), "dup");
+        let dup = make_file(&dir.join("b.txt"), "dup");
+
+        apply(Action::Hardlink, vec![vec![keeper, dup]], false);
+
+        let keeper_meta = fs::metadata(dir.join("a.txt")).unwrap();
+        let dup_meta = fs::metadata(dir.join("b.txt")).unwrap();
+        assert_eq!(keeper_meta.ino(), dup_meta.ino());
+    }
+
+    #[test]
+    fn symlink_replaces_non_keeper_files() {
+        let dir = scratch_dir("symlink");
+        let keeper = make_file(&dir.join("a.txt"), "dup");
+        let dup = make_file(&dir.join("b.txt"), "dup");
+
+        apply(Action::Symlink, vec![vec![keeper, dup]], false);
+
+        let target = fs::read_link(dir.join("b.txt")).unwrap();
+        assert_eq!(target, dir.join("a.txt"));
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+/// A BK-tree over values compared by Hamming distance, giving sub-linear
+/// nearest-neighbor lookups instead of comparing a query against every item.
+pub struct BkTree<T> {
+    root: Option<Node<T>>,
+}
+
+struct Node<T> {
+    hash: u64,
+    item: T,
+    children: HashMap<u32, Box<Node<T>>>,
+}
+
+impl<T> BkTree<T> {
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+
+    pub fn insert(&mut self, hash: u64, item: T) {
+        match &mut self.root {
+            None => {
+                self.root = Some(Node {
+                    hash,
+                    item,
+                    children: HashMap::new(),
+                });
+            }
+            Some(root) => Self::insert_node(root, hash, item),
+        }
+    }
+
+    fn insert_node(node: &mut Node<T>, hash: u64, item: T) {
+        let distance = hamming(node.hash, hash);
+        match node.children.get_mut(&distance) {
+            Some(child) => Self::insert_node(child, hash, item),
+            None => {
+                node.children.insert(
+                    distance,
+                    Box::new(Node {
+                        hash,
+                        item,
+                        children: HashMap::new(),
+                    }),
+                );
+            }
+        }
+    }
+
+    /// Return every item within `threshold` Hamming distance of `query`, pruning
+    /// children whose distance to the parent can't fall within range.
+    pub fn find_within(&self, query: u64, threshold: u32) -> Vec<(&T, u32)> {
+        let mut results = Vec::new();
+        if let Some(root) = &self.root {
+            Self::search_node(root, query, threshold, &mut results);
+        }
+        results
+    }
+
+    fn search_node<'a>(
+        node: &'a Node<T>,
+        query: u64,
+        threshold: u32,
+        results: &mut Vec<(&'a T, u32)>,
+    ) {
+        let distance = hamming(node.hash, query);
+        if distance <= threshold {
+            results.push((&node.item, distance));
+        }
+        let lower = distance.saturating_sub(threshold);
+        let upper = distance + threshold;
+        for (&child_distance, child) in &node.children {
+            if child_distance >= lower && child_distance <= upper {
+                Self::search_node(child, query, threshold, results);
+            }
+        }
+    }
+}
+
+fn hamming(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_within_returns_only_items_inside_threshold() {
+        let mut tree = BkTree::new();
+        tree.insert(0b0000, "zero");
+        tree.insert(0b0001, "one-bit");
+        tree.insert(0b0111, "three-bit");
+
+        let mut hits: Vec<&str> = tree
+            .find_within(0b0000, 1)
+            .into_iter()
+            .map(|(item, _)| *item)
+            .collect();
+        hits.sort();
+
+        assert_eq!(hits, vec!["one-bit", "zero"]);
+    }
+
+    #[test]
+    fn find_within_excludes_items_right_at_threshold_plus_one() {
+        let mut tree = BkTree::new();
+        tree.insert(0b0000, "zero");
+        tree.insert(0b0011, "two-bit");
+
+        let hits = tree.find_within(0b0000, 1);
+        assert!(hits.iter().all(|(item, _)| **item != "two-bit"));
+    }
+
+    #[test]
+    fn find_within_on_empty_tree_returns_nothing() {
+        let tree: BkTree<&str> = BkTree::new();
+        assert!(tree.find_within(0, 64).is_empty());
+    }
+
+    #[test]
+    fn find_within_reports_correct_distance() {
+        let mut tree = BkTree::new();
+        tree.insert(0b1010, "item");
+
+        let hits = tree.find_within(0b1000, 4);
+        assert_eq!(hits, vec![(&"item", 1)]);
+    }
+}
@@ -0,0 +1,174 @@
+use std::path::Path;
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+/// Compiles the `--include`/`--exclude` CLI patterns into gitignore-style glob
+/// matchers and applies them (plus the dotfile skip) while walking the tree.
+///
+/// `exclude` (and the dotfile skip) prune recursion: a directory they match is
+/// never descended into, which is the performance win. This relies on the
+/// `ignore` crate's `Gitignore::matched`, which (unlike a plain `GlobSet`) is
+/// told whether each candidate is a directory, so a pattern like
+/// `**/node_modules/**` still matches the bare `node_modules` directory path
+/// itself and prunes it, rather than only matching the files underneath it
+/// one by one after the walk has already descended. `include`, by contrast,
+/// only gates which *files* end up in the tree — a directory named `sub`
+/// doesn't itself match `*.jpg`, so applying `include` to directories would
+/// prune every subdirectory and silently drop nested matches.
+pub struct PathFilter {
+    include: Option<GlobSet>,
+    exclude: Option<Gitignore>,
+}
+
+impl PathFilter {
+    pub fn new(include: &str, exclude: &str) -> Self {
+        Self {
+            include: build_glob_set(include),
+            exclude: build_gitignore(exclude),
+        }
+    }
+
+    /// Should this path be pruned outright (and, for a directory, not recursed into)?
+    pub fn should_skip(&self, path: &Path, is_dir: bool) -> bool {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if name.starts_with('.') {
+            return true;
+        }
+        if let Some(exclude) = &self.exclude {
+            if exclude.matched(path, is_dir).is_ignore() {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Should this *file* be left out of the tree for not matching `--include`?
+    /// Never applied to directories, so the walk still reaches their contents.
+    pub fn excludes_file(&self, path: &Path) -> bool {
+        match &self.include {
+            Some(include) => !include.is_match(path),
+            None => false,
+        }
+    }
+}
+
+/// Patterns are comma-separated (`*.tmp,**/node_modules/**`); an empty string
+/// means "no filter", matching the CLI's existing default.
+fn build_glob_set(patterns: &str) -> Option<GlobSet> {
+    if patterns.is_empty() {
+        return None;
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+        if let Ok(glob) = Glob::new(pattern) {
+            builder.add(glob);
+        }
+    }
+    builder.build().ok()
+}
+
+/// Patterns are comma-separated, same as `build_glob_set`. Built with the
+/// `ignore` crate instead of `globset` so a directory is matched with
+/// knowledge that it *is* a directory (`Gitignore::matched(path, is_dir)`),
+/// letting recursion actually stop there instead of only filtering the files
+/// found underneath it one by one.
+///
+/// A pattern like `**/node_modules/**` only matches paths *under*
+/// `node_modules`, never the `node_modules` directory itself — that's true
+/// gitignore semantics, not a bug in the matcher. So for every pattern
+/// ending in `/**` we also register the directory-only variant with that
+/// suffix stripped, so the directory itself is pruned too.
+fn build_gitignore(patterns: &str) -> Option<Gitignore> {
+    if patterns.is_empty() {
+        return None;
+    }
+    let mut builder = GitignoreBuilder::new(".");
+    let mut added = false;
+    for pattern in patterns.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+        if builder.add_line(None, pattern).is_ok() {
+            added = true;
+        }
+        if let Some(dir_pattern) = pattern.strip_suffix("/**") {
+            if !dir_pattern.is_empty() && builder.add_line(None, dir_pattern).is_ok() {
+                added = true;
+            }
+        }
+    }
+    if !added {
+        return None;
+    }
+    builder.build().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn scratch_dir(label: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("fileorganizer-filter-test-{}-{}", label, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn exclude_prunes_matching_directory_itself() {
+        let dir = scratch_dir("prune-dir");
+        let node_modules = dir.join("node_modules");
+        fs::create_dir_all(&node_modules).unwrap();
+
+        let filter = PathFilter::new("", "**/node_modules/**");
+        assert!(filter.should_skip(&node_modules, true));
+    }
+
+    #[test]
+    fn exclude_does_not_prune_unrelated_directory() {
+        let dir = scratch_dir("keep-dir");
+        let src = dir.join("src");
+        fs::create_dir_all(&src).unwrap();
+
+        let filter = PathFilter::new("", "**/node_modules/**");
+        assert!(!filter.should_skip(&src, true));
+    }
+
+    #[test]
+    fn exclude_still_matches_files_under_excluded_pattern() {
+        let dir = scratch_dir("exclude-file");
+        let file = dir.join("notes.tmp");
+        fs::write(&file, "x").unwrap();
+
+        let filter = PathFilter::new("", "*.tmp");
+        assert!(filter.should_skip(&file, false));
+    }
+
+    #[test]
+    fn include_gates_files_but_not_directories() {
+        let dir = scratch_dir("include-dir");
+        let sub = dir.join("sub");
+        fs::create_dir_all(&sub).unwrap();
+        let jpg = sub.join("photo.jpg");
+        let txt = sub.join("notes.txt");
+        fs::write(&jpg, "x").unwrap();
+        fs::write(&txt, "x").unwrap();
+
+        let filter = PathFilter::new("*.jpg", "");
+        assert!(!filter.should_skip(&sub, true));
+        assert!(!filter.excludes_file(&jpg));
+        assert!(filter.excludes_file(&txt));
+    }
+
+    #[test]
+    fn dotfiles_are_always_skipped() {
+        let dir = scratch_dir("dotfile");
+        let hidden = dir.join(".hidden");
+        fs::write(&hidden, "x").unwrap();
+
+        let filter = PathFilter::new("", "");
+        assert!(filter.should_skip(&hidden, false));
+    }
+}
@@ -0,0 +1,225 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+
+use clap::ValueEnum;
+
+/// Number of leading bytes read for a partial hash, before committing to a full digest.
+pub const BLOCK_SIZE: usize = 4096;
+
+/// Which hashing algorithm to use when comparing file contents.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashType {
+    Sha256,
+    Blake3,
+    Xxh3,
+    Crc32,
+}
+
+impl fmt::Display for HashType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HashType::Sha256 => "sha256",
+            HashType::Blake3 => "blake3",
+            HashType::Xxh3 => "xxh3",
+            HashType::Crc32 => "crc32",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl HashType {
+    /// Build the `Hasher` implementation for this algorithm.
+    pub fn hasher(&self) -> Box<dyn Hasher> {
+        match self {
+            HashType::Sha256 => Box::new(Sha256Hasher),
+            HashType::Blake3 => Box::new(Blake3Hasher),
+            HashType::Xxh3 => Box::new(Xxh3Hasher),
+            HashType::Crc32 => Box::new(Crc32Hasher),
+        }
+    }
+}
+
+/// A content digest algorithm, usable for both the partial (first-block) and
+/// full-file passes of the dedupe pipeline.
+pub trait Hasher: Send + Sync {
+    fn digest_file(&self, path: &Path) -> io::Result<String>;
+    fn digest_bytes(&self, data: &[u8]) -> String;
+}
+
+struct Sha256Hasher;
+
+impl Hasher for Sha256Hasher {
+    fn digest_file(&self, path: &Path) -> io::Result<String> {
+        sha256::try_digest(path)
+    }
+
+    fn digest_bytes(&self, data: &[u8]) -> String {
+        sha256::digest(data)
+    }
+}
+
+struct Blake3Hasher;
+
+impl Hasher for Blake3Hasher {
+    fn digest_file(&self, path: &Path) -> io::Result<String> {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update_reader(fs::File::open(path)?)?;
+        Ok(hasher.finalize().to_hex().to_string())
+    }
+
+    fn digest_bytes(&self, data: &[u8]) -> String {
+        blake3::hash(data).to_hex().to_string()
+    }
+}
+
+struct Xxh3Hasher;
+
+impl Hasher for Xxh3Hasher {
+    fn digest_file(&self, path: &Path) -> io::Result<String> {
+        use std::hash::Hasher as _;
+        let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+        stream_file(path, |chunk| hasher.write(chunk))?;
+        Ok(format!("{:016x}", hasher.finish()))
+    }
+
+    fn digest_bytes(&self, data: &[u8]) -> String {
+        format!("{:016x}", xxhash_rust::xxh3::xxh3_64(data))
+    }
+}
+
+struct Crc32Hasher;
+
+impl Hasher for Crc32Hasher {
+    fn digest_file(&self, path: &Path) -> io::Result<String> {
+        let mut hasher = crc32fast::Hasher::new();
+        stream_file(path, |chunk| hasher.update(chunk))?;
+        Ok(format!("{:08x}", hasher.finalize()))
+    }
+
+    fn digest_bytes(&self, data: &[u8]) -> String {
+        format!("{:08x}", crc32fast::hash(data))
+    }
+}
+
+/// Read `path` in fixed-size chunks, feeding each into `update`, instead of
+/// buffering the whole file in memory — matters for the non-cryptographic
+/// hashes, which exist specifically to make large-file hashing cheap.
+fn stream_file<U>(path: &Path, mut update: U) -> io::Result<()>
+where
+    U: FnMut(&[u8]),
+{
+    let mut f = fs::File::open(path)?;
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = f.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        update(&buf[..n]);
+    }
+    Ok(())
+}
+
+/// Read just the first `BLOCK_SIZE` bytes of a file, for the partial-hash pass.
+pub fn read_block(path: &Path) -> io::Result<Vec<u8>> {
+    let mut f = fs::File::open(path)?;
+    let mut buf = [0u8; BLOCK_SIZE];
+    let n = f.read(&mut buf)?;
+    Ok(buf[..n].to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn scratch_dir(label: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("fileorganizer-hashing-test-{}-{}", label, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn all_hashers() -> Vec<Box<dyn Hasher>> {
+        vec![
+            HashType::Sha256.hasher(),
+            HashType::Blake3.hasher(),
+            HashType::Xxh3.hasher(),
+            HashType::Crc32.hasher(),
+        ]
+    }
+
+    #[test]
+    fn digest_file_agrees_with_digest_bytes_for_each_algorithm() {
+        let dir = scratch_dir("file-vs-bytes");
+        let path = dir.join("a.txt");
+        let contents = b"the quick brown fox";
+        fs::write(&path, contents).unwrap();
+
+        for hasher in all_hashers() {
+            assert_eq!(
+                hasher.digest_file(&path).unwrap(),
+                hasher.digest_bytes(contents)
+            );
+        }
+    }
+
+    #[test]
+    fn digest_file_matches_across_a_chunk_boundary() {
+        // Exercises stream_file's 64KB read loop for the streaming hashers.
+        let dir = scratch_dir("chunked");
+        let path = dir.join("big.bin");
+        let contents = vec![0xab_u8; 64 * 1024 + 17];
+        fs::write(&path, &contents).unwrap();
+
+        for hasher in all_hashers() {
+            assert_eq!(
+                hasher.digest_file(&path).unwrap(),
+                hasher.digest_bytes(&contents)
+            );
+        }
+    }
+
+    #[test]
+    fn digest_file_handles_empty_file() {
+        let dir = scratch_dir("empty");
+        let path = dir.join("empty.txt");
+        fs::write(&path, []).unwrap();
+
+        for hasher in all_hashers() {
+            assert_eq!(hasher.digest_file(&path).unwrap(), hasher.digest_bytes(&[]));
+        }
+    }
+
+    #[test]
+    fn read_block_truncates_to_block_size() {
+        let dir = scratch_dir("block-truncate");
+        let path = dir.join("big.bin");
+        fs::write(&path, vec![1u8; BLOCK_SIZE * 2]).unwrap();
+
+        assert_eq!(read_block(&path).unwrap().len(), BLOCK_SIZE);
+    }
+
+    #[test]
+    fn read_block_returns_whole_file_when_smaller_than_block_size() {
+        let dir = scratch_dir("block-small");
+        let path = dir.join("small.bin");
+        fs::write(&path, vec![2u8; BLOCK_SIZE / 2]).unwrap();
+
+        assert_eq!(read_block(&path).unwrap().len(), BLOCK_SIZE / 2);
+    }
+
+    #[test]
+    fn read_block_handles_empty_file() {
+        let dir = scratch_dir("block-empty");
+        let path = dir.join("empty.bin");
+        fs::write(&path, []).unwrap();
+
+        assert!(read_block(&path).unwrap().is_empty());
+    }
+}
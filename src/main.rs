@@ -1,13 +1,23 @@
+mod actions;
+mod bktree;
+mod filter;
+mod hashing;
+mod phash;
+mod report;
+
 use clap::Parser;
 
 use std::collections::hash_map::Iter;
 use std::collections::HashMap;
-use std::ffi::OsStr;
 use std::fs;
 use std::io;
 use std::path::Path;
 
-use sha256::try_digest;
+use actions::Action;
+use filter::PathFilter;
+use hashing::{HashType, Hasher};
+use rayon::prelude::*;
+use report::OutputFormat;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -16,19 +26,50 @@ struct Args {
     #[arg(short, long)]
     dir: String,
 
-    /// Exclude pattern
+    /// Comma-separated gitignore-style glob patterns to exclude (e.g. "*.tmp,**/node_modules/**")
     #[arg(short, long, default_value = "", required = false)]
     exclude: String,
 
-    /// Include pattern
+    /// Comma-separated gitignore-style glob patterns to include
     #[arg(short, long, default_value = "", required = false)]
     include: String,
+
+    /// Hash algorithm used to compare file contents
+    #[arg(long, value_enum, default_value_t = HashType::Sha256)]
+    hash_algo: HashType,
+
+    /// Number of worker threads used for hashing (defaults to rayon's own choice)
+    #[arg(long)]
+    threads: Option<usize>,
+
+    /// Also find visually similar (not just byte-identical) images, grouping
+    /// perceptual hashes within this many bits of Hamming distance (0-64)
+    #[arg(long)]
+    similarity: Option<u32>,
+
+    /// What to do with files that are byte-for-byte duplicates
+    #[arg(long, value_enum, default_value_t = Action::Report)]
+    action: Action,
+
+    /// Output format for the duplicate report
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+}
+
+/// Which stage of the hashing pipeline a digest on `File` was computed at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashMode {
+    Partial,
+    Full,
 }
 
 #[derive(Debug, Clone)]
 pub struct File {
     pub name: String,
     pub metadata: fs::Metadata,
+    /// Cached partial digest (first `BLOCK_SIZE` bytes), so size-bucketed files that
+    /// already collided on a partial hash don't get re-read for it.
+    pub partial_hash: Option<String>,
 }
 
 #[derive(Debug)]
@@ -98,7 +139,7 @@ impl FileIndex {
     }
 }
 
-fn walk_dir(dir: &Path, filter: fn(name: &str) -> bool) -> io::Result<Directory> {
+fn walk_dir(dir: &Path, filter: &PathFilter) -> io::Result<Directory> {
     //println!("walk_dir {}", dir.to_str().unwrap());
 
     let entries: Vec<fs::DirEntry> = fs::read_dir(dir)?
@@ -109,14 +150,9 @@ fn walk_dir(dir: &Path, filter: fn(name: &str) -> bool) -> io::Result<Directory>
 
     for entry in entries {
         let path = entry.path();
-        let name: String = path
-            .file_name()
-            .unwrap_or(OsStr::new("."))
-            .to_str()
-            .unwrap_or(".")
-            .into();
-        //println!("iter {}", name);
-        if !filter(&name) {
+        //println!("iter {}", path.to_str().unwrap_or("."));
+        let is_dir = path.is_dir();
+        if filter.should_skip(&path, is_dir) {
             continue;
         };
         let metadata = fs::metadata(&path).unwrap();
@@ -127,10 +163,16 @@ fn walk_dir(dir: &Path, filter: fn(name: &str) -> bool) -> io::Result<Directory>
                 target: fs::read_link(path).unwrap().to_string_lossy().to_string(),
                 metadata: metadata,
             }),
-            path if path.is_file() => FileTree::FileNode(File {
-                name: path.to_str().unwrap().into(),
-                metadata: metadata,
-            }),
+            path if path.is_file() => {
+                if filter.excludes_file(&path) {
+                    continue;
+                }
+                FileTree::FileNode(File {
+                    name: path.to_str().unwrap().into(),
+                    metadata: metadata,
+                    partial_hash: None,
+                })
+            }
             _ => unreachable!(),
         };
         directory.push(node);
@@ -142,10 +184,6 @@ fn walk_dir(dir: &Path, filter: fn(name: &str) -> bool) -> io::Result<Directory>
     })
 }
 
-fn should_skip(file_name: &str) -> bool {
-    return !file_name.starts_with(".");
-}
-
 fn visit_files<F>(node: &Directory, func: &mut F)
 where
     F: FnMut(&File),
@@ -165,12 +203,77 @@ where
     }
 }
 
-fn create_hash_index(node: &Directory, file_index: &mut FileIndex) -> () {
-    let mut visitor = |file: &File| -> () {
-        let digest = try_digest(Path::new(&file.name)).unwrap();
-        file_index.store_hash(digest, file.clone());
-    };
-    visit_files(node, &mut visitor);
+/// Hash `file` for the given `mode` using `hasher`, reusing a cached partial digest
+/// instead of re-reading the file when one is already present.
+fn compute_hash(file: &mut File, mode: HashMode, hasher: &dyn Hasher) -> io::Result<String> {
+    match mode {
+        HashMode::Partial => {
+            if let Some(cached) = &file.partial_hash {
+                return Ok(cached.clone());
+            }
+            let block = hashing::read_block(Path::new(&file.name))?;
+            let digest = hasher.digest_bytes(&block);
+            file.partial_hash = Some(digest.clone());
+            Ok(digest)
+        }
+        HashMode::Full => hasher.digest_file(Path::new(&file.name)),
+    }
+}
+
+/// Three-stage dedupe pipeline: bucket by file size (free), then by partial hash
+/// (cheap), and only compute a full digest for files that survive both rounds.
+/// All three stages run through the same `hasher`, so switching `--hash-algo`
+/// changes both the partial and full passes together.
+///
+/// The hashing rounds (stages 2 and 3) run on a rayon thread pool: each worker
+/// hashes its own file and returns a `(key, File)` pair, and the pairs are
+/// merged into `by_partial`/`file_index` afterward, since `FileIndex::store_hash`
+/// takes `&mut self` and can't be shared across threads directly.
+fn create_hash_index(node: &Directory, file_index: &mut FileIndex, hasher: &dyn Hasher) {
+    let mut files: Vec<File> = Vec::new();
+    visit_files(node, &mut |file: &File| files.push(file.clone()));
+
+    let mut by_size: HashMap<u64, Vec<File>> = HashMap::new();
+    for file in files {
+        by_size.entry(file.metadata.len()).or_default().push(file);
+    }
+
+    let partial_candidates: Vec<File> = by_size
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .flatten()
+        .collect();
+
+    let partial_results: Vec<(u64, String, File)> = partial_candidates
+        .into_par_iter()
+        .map(|mut file| {
+            let partial = compute_hash(&mut file, HashMode::Partial, hasher).unwrap();
+            (file.metadata.len(), partial, file)
+        })
+        .collect();
+
+    let mut by_partial: HashMap<(u64, String), Vec<File>> = HashMap::new();
+    for (size, partial, file) in partial_results {
+        by_partial.entry((size, partial)).or_default().push(file);
+    }
+
+    let full_candidates: Vec<File> = by_partial
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .flatten()
+        .collect();
+
+    let hashed: Vec<(String, File)> = full_candidates
+        .into_par_iter()
+        .map(|mut file| {
+            let digest = compute_hash(&mut file, HashMode::Full, hasher).unwrap();
+            (digest, file)
+        })
+        .collect();
+
+    for (digest, file) in hashed {
+        file_index.store_hash(digest, file);
+    }
 }
 
 fn create_name_index(node: &Directory, file_index: & mut FileIndex) -> () {
@@ -181,15 +284,68 @@ fn create_name_index(node: &Directory, file_index: & mut FileIndex) -> () {
     visit_files(node, &mut visitor);
 }
 
-fn organize(dir: &Path) -> i32 {
-    let tree = walk_dir(dir, should_skip);
+/// Drop files that no longer exist (e.g. removed by `actions::apply`) from
+/// duplicate groups, and drop any group left with fewer than 2 members.
+fn prune_deleted(groups: Vec<(String, Vec<File>)>) -> Vec<(String, Vec<File>)> {
+    groups
+        .into_iter()
+        .filter_map(|(key, files)| {
+            let remaining: Vec<File> = files
+                .into_iter()
+                .filter(|file| Path::new(&file.name).exists())
+                .collect();
+            if remaining.len() > 1 {
+                Some((key, remaining))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn organize(
+    dir: &Path,
+    hasher: &dyn Hasher,
+    similarity: Option<u32>,
+    filter: &PathFilter,
+    action: Action,
+    format: OutputFormat,
+) -> i32 {
+    let tree = walk_dir(dir, filter);
     match tree {
         Ok(tree) => {
             let mut file_index = FileIndex::new();
-            create_hash_index(&tree, &mut file_index);
-            for h in file_index.get_hashes() {
-                let collisions = h.1;
-                if collisions.len() > 1 {
+            create_hash_index(&tree, &mut file_index, hasher);
+
+            let hash_groups: Vec<(String, Vec<File>)> = file_index
+                .get_hashes()
+                .filter(|(_, files)| files.len() > 1)
+                .map(|(hash, files)| (hash.clone(), files.clone()))
+                .collect();
+            actions::apply(
+                action,
+                hash_groups.iter().map(|(_, files)| files.clone()).collect(),
+                format == OutputFormat::Json,
+            );
+
+            create_name_index(&tree, &mut file_index);
+
+            let name_groups: Vec<(String, Vec<File>)> = file_index
+                .get_names()
+                .filter(|(_, files)| files.len() > 1)
+                .map(|(name, files)| (name.clone(), files.clone()))
+                .collect();
+
+            // actions::apply may have already deleted/replaced files from
+            // hash_groups above, so drop any path that no longer exists
+            // before reporting either group — otherwise a caller (especially
+            // a --format json consumer) could be handed a path to act on
+            // that's already gone.
+            let hash_groups = prune_deleted(hash_groups);
+            let name_groups = prune_deleted(name_groups);
+
+            if format == OutputFormat::Text {
+                for (_, collisions) in &name_groups {
                     println!("Multiple matches for {}", collisions.first().unwrap().name);
                     collisions.iter().for_each(|item| {
                         println!("\t {}", item.name);
@@ -197,13 +353,31 @@ fn organize(dir: &Path) -> i32 {
                 }
             }
 
-            create_name_index(&tree, &mut file_index);
+            if format == OutputFormat::Json {
+                let mut report = report::Report::default();
+                for (hash, files) in &hash_groups {
+                    report.hash_duplicates.push(report::build_group(hash, files));
+                }
+                for (name, files) in &name_groups {
+                    report.name_duplicates.push(report::build_group(name, files));
+                }
+                println!("{}", serde_json::to_string_pretty(&report).unwrap());
+            }
 
-            for h in file_index.get_names() {
-                let collisions = h.1;
-                if collisions.len() > 1 {
-                    println!("Multiple matches for {}", collisions.first().unwrap().name);
-                    collisions.iter().for_each(|item| {
+            if let Some(threshold) = similarity {
+                let mut images: Vec<File> = Vec::new();
+                visit_files(&tree, &mut |file: &File| {
+                    if phash::is_image(&file.name) {
+                        images.push(file.clone());
+                    }
+                });
+                for cluster in phash::find_similar(&images, threshold) {
+                    println!(
+                        "Similar images for {} (within {} bits)",
+                        cluster.first().unwrap().name,
+                        threshold
+                    );
+                    cluster.iter().for_each(|item| {
                         println!("\t {}", item.name);
                     });
                 }
@@ -221,7 +395,116 @@ fn organize(dir: &Path) -> i32 {
 fn main() {
     let args = Args::parse();
 
-    println!("Searching {}!", args.dir);
+    if args.format == OutputFormat::Text {
+        println!("Searching {}!", args.dir);
+    }
+
+    if let Some(threads) = args.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .expect("failed to configure rayon thread pool");
+    }
+
+    let hasher = args.hash_algo.hasher();
+    let filter = PathFilter::new(&args.include, &args.exclude);
+    organize(
+        Path::new(&args.dir),
+        hasher.as_ref(),
+        args.similarity,
+        &filter,
+        args.action,
+        args.format,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::*;
+    use hashing::HashType;
+
+    fn scratch_dir(label: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("fileorganizer-main-test-{}-{}", label, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn build_hash_index(dir: &Path) -> FileIndex {
+        let filter = PathFilter::new("", "");
+        let tree = walk_dir(dir, &filter).unwrap();
+        let mut file_index = FileIndex::new();
+        let hasher = HashType::Sha256.hasher();
+        create_hash_index(&tree, &mut file_index, hasher.as_ref());
+        file_index
+    }
+
+    #[test]
+    fn same_size_different_content_is_not_flagged_as_duplicate() {
+        let dir = scratch_dir("same-size-diff-content");
+        fs::write(dir.join("a.txt"), "aaaa").unwrap();
+        fs::write(dir.join("b.txt"), "bbbb").unwrap();
+
+        let file_index = build_hash_index(&dir);
+        assert!(file_index.get_hashes().all(|(_, files)| files.len() < 2));
+    }
+
+    #[test]
+    fn identical_content_is_flagged_as_duplicate() {
+        let dir = scratch_dir("identical");
+        fs::write(dir.join("a.txt"), "same contents").unwrap();
+        fs::write(dir.join("b.txt"), "same contents").unwrap();
+
+        let file_index = build_hash_index(&dir);
+        assert!(file_index.get_hashes().any(|(_, files)| files.len() > 1));
+    }
 
-    organize(Path::new(&args.dir));
+    #[test]
+    fn files_smaller_than_block_size_are_compared_correctly() {
+        let dir = scratch_dir("small-files");
+        fs::write(dir.join("a.txt"), "x").unwrap();
+        fs::write(dir.join("b.txt"), "y").unwrap();
+
+        let file_index = build_hash_index(&dir);
+        assert!(file_index.get_hashes().all(|(_, files)| files.len() < 2));
+    }
+
+    #[test]
+    fn empty_files_are_flagged_as_duplicates_of_each_other() {
+        let dir = scratch_dir("empty-files");
+        fs::write(dir.join("a.txt"), "").unwrap();
+        fs::write(dir.join("b.txt"), "").unwrap();
+
+        let file_index = build_hash_index(&dir);
+        assert!(file_index.get_hashes().any(|(_, files)| files.len() > 1));
+    }
+
+    /// Large enough to spread across rayon's worker threads, with several
+    /// distinct duplicate groups mixed with unique files, so a merge bug in
+    /// either parallel stage would show up as a miscounted or merged group.
+    #[test]
+    fn parallel_hashing_stages_merge_results_correctly_across_many_files() {
+        let dir = scratch_dir("parallel-batch");
+        for group in 0..5 {
+            for copy in 0..4 {
+                fs::write(
+                    dir.join(format!("group{}-copy{}.txt", group, copy)),
+                    format!("contents of group {}", group),
+                )
+                .unwrap();
+            }
+        }
+        for unique in 0..10 {
+            fs::write(dir.join(format!("unique{}.txt", unique)), format!("unique {}", unique)).unwrap();
+        }
+
+        let file_index = build_hash_index(&dir);
+        let groups: Vec<usize> = file_index.get_hashes().map(|(_, files)| files.len()).collect();
+        assert_eq!(groups.len(), 5);
+        assert!(groups.iter().all(|&len| len == 4));
+    }
 }
@@ -0,0 +1,82 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use image::imageops::FilterType;
+
+use crate::bktree::BkTree;
+use crate::File;
+
+/// Resized to one extra column so each row yields 8 left/right comparisons.
+const HASH_WIDTH: u32 = 9;
+const HASH_HEIGHT: u32 = 8;
+
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif", "bmp", "webp", "tiff"];
+
+pub fn is_image(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Compute a 64-bit difference hash (dHash): downscale to a 9x8 grayscale grid
+/// and record, for each row, whether each pixel is brighter than its left neighbor.
+/// Visually similar images end up with hashes a small Hamming distance apart.
+pub fn dhash(path: &Path) -> Option<u64> {
+    let img = image::open(path).ok()?;
+    let small = img
+        .resize_exact(HASH_WIDTH, HASH_HEIGHT, FilterType::Triangle)
+        .to_luma8();
+
+    let mut hash: u64 = 0;
+    let mut bit = 0;
+    for y in 0..HASH_HEIGHT {
+        for x in 0..HASH_WIDTH - 1 {
+            let left = small.get_pixel(x, y)[0];
+            let right = small.get_pixel(x + 1, y)[0];
+            if left > right {
+                hash |= 1 << bit;
+            }
+            bit += 1;
+        }
+    }
+    Some(hash)
+}
+
+/// Group images whose perceptual hashes fall within `threshold` Hamming distance
+/// of one another, using a BK-tree for the neighbor lookups.
+pub fn find_similar(images: &[File], threshold: u32) -> Vec<Vec<&File>> {
+    let hashes: Vec<(u64, &File)> = images
+        .iter()
+        .filter_map(|file| dhash(Path::new(&file.name)).map(|hash| (hash, file)))
+        .collect();
+
+    let mut tree: BkTree<&File> = BkTree::new();
+    for (hash, file) in &hashes {
+        tree.insert(*hash, file);
+    }
+
+    let mut clustered: HashSet<*const File> = HashSet::new();
+    let mut clusters: Vec<Vec<&File>> = Vec::new();
+
+    for (hash, file) in &hashes {
+        if clustered.contains(&(*file as *const File)) {
+            continue;
+        }
+        let neighbors = tree.find_within(*hash, threshold);
+        if neighbors.len() < 2 {
+            continue;
+        }
+        let mut cluster = Vec::new();
+        for (neighbor, _distance) in neighbors {
+            if clustered.insert(*neighbor as *const File) {
+                cluster.push(*neighbor);
+            }
+        }
+        if cluster.len() > 1 {
+            clusters.push(cluster);
+        }
+    }
+    clusters
+}
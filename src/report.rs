@@ -0,0 +1,128 @@
+use std::fmt;
+use std::time::SystemTime;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::File;
+
+/// Output format for the duplicate report.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A serializable snapshot of a `File`, captured at index time since
+/// `fs::Metadata` itself isn't `Serialize`.
+#[derive(Serialize, Debug, Clone)]
+pub struct FileEntry {
+    pub path: String,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl From<&File> for FileEntry {
+    fn from(file: &File) -> Self {
+        Self {
+            path: file.name.clone(),
+            size: file.metadata.len(),
+            modified: file.metadata.modified().ok(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct DuplicateGroup {
+    pub key: String,
+    pub files: Vec<FileEntry>,
+    pub reclaimable_bytes: u64,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct Report {
+    pub hash_duplicates: Vec<DuplicateGroup>,
+    pub name_duplicates: Vec<DuplicateGroup>,
+}
+
+/// Build a `DuplicateGroup` from a key (hash or normalized name) and its
+/// members, treating every file but the first as reclaimable space.
+pub fn build_group(key: &str, files: &[File]) -> DuplicateGroup {
+    let entries: Vec<FileEntry> = files.iter().map(FileEntry::from).collect();
+    let reclaimable_bytes = entries.iter().skip(1).map(|entry| entry.size).sum();
+    DuplicateGroup {
+        key: key.to_string(),
+        files: entries,
+        reclaimable_bytes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn scratch_dir(label: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("fileorganizer-report-test-{}-{}", label, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn make_file(path: &std::path::Path, contents: &str) -> File {
+        fs::write(path, contents).unwrap();
+        File {
+            name: path.to_string_lossy().to_string(),
+            metadata: fs::metadata(path).unwrap(),
+            partial_hash: None,
+        }
+    }
+
+    #[test]
+    fn file_entry_captures_path_and_size() {
+        let dir = scratch_dir("file-entry");
+        let file = make_file(&dir.join("a.txt"), "hello");
+
+        let entry = FileEntry::from(&file);
+        assert_eq!(entry.path, file.name);
+        assert_eq!(entry.size, 5);
+    }
+
+    #[test]
+    fn build_group_sums_reclaimable_bytes_for_all_but_first_file() {
+        let dir = scratch_dir("build-group");
+        let keeper = make_file(&dir.join("a.txt"), "dup12345");
+        let dup1 = make_file(&dir.join("b.txt"), "dup12345");
+        let dup2 = make_file(&dir.join("c.txt"), "dup12345");
+
+        let group = build_group("somehash", &[keeper, dup1, dup2]);
+
+        assert_eq!(group.key, "somehash");
+        assert_eq!(group.files.len(), 3);
+        assert_eq!(group.reclaimable_bytes, 16);
+    }
+
+    #[test]
+    fn build_group_on_single_file_has_no_reclaimable_bytes() {
+        let dir = scratch_dir("single-file");
+        let only = make_file(&dir.join("a.txt"), "solo");
+
+        let group = build_group("somehash", &[only]);
+
+        assert_eq!(group.reclaimable_bytes, 0);
+    }
+}